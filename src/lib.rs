@@ -4,13 +4,28 @@
 //! Useful for tracking the number of holders exist for a handle,
 //! tracking the number of transactions that are in-flight, etc.
 //!
+//! Works under `#![no_std]` (with `alloc`) for the counting APIs and the async notify
+//! APIs. The `std` feature, enabled by default, additionally unlocks the blocking
+//! `wait_until_condition` family and [`NotifyHandle::select`], which need OS thread
+//! parking and timeouts.
+//!
 //! # Additional Features
 //! * [`Counter`]s can have a size, eg. a [`Counter`] with `size` 4 adds 4
 //! to the count, and removes 4 when dropped.
 //! * [`NotifyHandle`]s can be used for efficient conditional checking, eg.
 //! if you want to wait until there are no in-flight transactions, see:
 //! [`CounterBuilder::create_notify`] / [`WeakCounterBuilder::create_notify`]
-//! and [`NotifyHandle::wait_until_condition`].
+//! and [`NotifyHandle::wait_until_condition`] (requires the `std` feature).
+//! * [`NotifyHandle::wait_until_condition_async`] provides the same functionality for
+//! callers running inside an async runtime, without blocking an OS thread, and is
+//! available even without the `std` feature.
+//! * [`NotifyHandle::changed`] / [`NotifyHandle::current`] offer a lower-level watch-style
+//! primitive for callers that want to observe every change rather than a fixed condition.
+//! * [`NotifyHandle::select`] waits on multiple handles at once, returning the index of
+//! whichever one's condition is satisfied first.
+//! * [`NotifyHandle::check_condition`] / [`NotifyHandle::is_connected`] / [`NotifyHandle::waiter_count`]
+//! and [`Counter::notify_handle_count`] / [`WeakCounter::notify_handle_count`] let callers
+//! poll and introspect without blocking.
 //!
 //! # Demo
 //!
@@ -34,14 +49,23 @@
 //! assert_eq!(weak.count(), 0);
 //! ```
 
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
+use core::fmt::{self, Display, Formatter};
+use core::sync::atomic::{AtomicUsize, Ordering};
 use notify::NotifySender;
-use std::fmt::{self, Display, Formatter};
-use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
 use std::sync::Arc;
 
 mod notify;
 
-pub use notify::{NotifyError, NotifyHandle, NotifyTimeoutError};
+#[cfg(feature = "std")]
+pub use notify::NotifyTimeoutError;
+pub use notify::{NotifyError, NotifyHandle};
 
 /// Essentially an AtomicUsize that is clonable and whose count is based
 /// on the number of copies (and their size). The count is automatically updated on Drop.
@@ -51,7 +75,7 @@ pub use notify::{NotifyError, NotifyHandle, NotifyTimeoutError};
 #[derive(Debug)]
 pub struct Counter {
     counter: Arc<AtomicUsize>,
-    notify: Vec<NotifySender>,
+    notify: NotifySender,
     size: usize,
 }
 
@@ -59,14 +83,14 @@ pub struct Counter {
 #[derive(Clone, Debug)]
 pub struct WeakCounter {
     counter: Arc<AtomicUsize>,
-    notify: Vec<NotifySender>,
+    notify: NotifySender,
 }
 
 /// A builder for the [`Counter`].
 pub struct CounterBuilder {
     counter: Arc<AtomicUsize>,
     size: usize,
-    notify: Vec<NotifySender>,
+    notify: NotifySender,
 }
 
 impl CounterBuilder {
@@ -84,9 +108,7 @@ impl CounterBuilder {
     /// [`NotifyHandle`]s cannot be associated after creation, since all linked
     /// [`Counter`] / [`WeakCounter`]s cannot be accounted for.
     pub fn create_notify(&mut self) -> NotifyHandle {
-        let (handle, sender) = NotifyHandle::new(Arc::clone(&self.counter));
-        self.notify.push(sender);
-        handle
+        self.notify.create_handle(Arc::clone(&self.counter))
     }
 
     /// Create a new [`Counter`].
@@ -105,7 +127,7 @@ impl Default for CounterBuilder {
         Self {
             counter: Arc::new(AtomicUsize::new(0)),
             size: 1,
-            notify: vec![],
+            notify: NotifySender::new(),
         }
     }
 }
@@ -136,14 +158,18 @@ impl Counter {
     pub fn count(&self) -> usize {
         self.counter.load(Ordering::Acquire)
     }
+
+    /// The number of [`NotifyHandle`]s currently attached to this count, eg. for
+    /// asserting there are no leaked handles in tests.
+    pub fn notify_handle_count(&self) -> usize {
+        self.notify.handle_count()
+    }
 }
 
 impl Clone for Counter {
     fn clone(&self) -> Self {
         self.counter.fetch_add(self.size, Ordering::SeqCst);
-        for sender in &self.notify {
-            sender.notify();
-        }
+        self.notify.notify();
         Counter {
             notify: self.notify.clone(),
             counter: Arc::clone(&self.counter),
@@ -161,16 +187,14 @@ impl Display for Counter {
 impl Drop for Counter {
     fn drop(&mut self) {
         self.counter.fetch_sub(self.size, Ordering::SeqCst);
-        for sender in &self.notify {
-            sender.notify();
-        }
+        self.notify.notify();
     }
 }
 
 /// A builder for the [`WeakCounter`].
 pub struct WeakCounterBuilder {
     counter: Arc<AtomicUsize>,
-    notify: Vec<NotifySender>,
+    notify: NotifySender,
 }
 
 impl WeakCounterBuilder {
@@ -180,9 +204,7 @@ impl WeakCounterBuilder {
     /// [`NotifyHandle`]s cannot be associated after creation, since all linked
     /// [`Counter`] / [`WeakCounter`]s cannot be accounted for.
     pub fn create_notify(&mut self) -> NotifyHandle {
-        let (handle, sender) = NotifyHandle::new(Arc::clone(&self.counter));
-        self.notify.push(sender);
-        handle
+        self.notify.create_handle(Arc::clone(&self.counter))
     }
 
     /// Create a new [`WeakCounter`]. This [`WeakCounter`] creates a new count
@@ -199,7 +221,7 @@ impl Default for WeakCounterBuilder {
     fn default() -> Self {
         Self {
             counter: Arc::new(AtomicUsize::new(0)),
-            notify: vec![],
+            notify: NotifySender::new(),
         }
     }
 }
@@ -217,6 +239,12 @@ impl WeakCounter {
         self.counter.load(Ordering::Acquire)
     }
 
+    /// The number of [`NotifyHandle`]s currently attached to this count, eg. for
+    /// asserting there are no leaked handles in tests.
+    pub fn notify_handle_count(&self) -> usize {
+        self.notify.handle_count()
+    }
+
     /// Consumes self, becomes a [`Counter`] of `size` 1.
     pub fn upgrade(self) -> Counter {
         self.spawn_upgrade()
@@ -232,9 +260,7 @@ impl WeakCounter {
     /// current [`WeakCounter`].
     pub fn spawn_upgrade_with_size(&self, size: usize) -> Counter {
         self.counter.fetch_add(size, Ordering::SeqCst);
-        for sender in &self.notify {
-            sender.notify();
-        }
+        self.notify.notify();
         Counter {
             notify: self.notify.clone(),
             counter: Arc::clone(&self.counter),
@@ -249,7 +275,9 @@ impl Display for WeakCounter {
     }
 }
 
-#[cfg(test)]
+// The test harness itself needs `std`, and most of these tests exercise the blocking
+// `wait_until_condition`/`select` APIs, which are `std`-only.
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     use std::thread;
@@ -412,4 +440,163 @@ mod tests {
         // Counters are not dropped until here.
         drop(weak);
     }
+
+    #[test]
+    fn wait_until_condition_async_works() {
+        run_wait_until_condition_test(|notify| {
+            futures_lite::future::block_on(notify.wait_until_condition_async(|v| v == 10)).unwrap()
+        });
+    }
+
+    #[test]
+    fn wait_until_condition_async_with_timeout_works() {
+        run_wait_until_condition_test(|notify| {
+            futures_lite::future::block_on(
+                notify.wait_until_condition_async_timeout(|v| v == 10, Duration::from_secs(2)),
+            )
+            .unwrap()
+        });
+    }
+
+    #[test]
+    fn notify_async_with_timeout_can_timeout() {
+        let (weak, notify) = {
+            let mut builder = WeakCounter::builder();
+            let notify = builder.create_notify();
+            (builder.build(), notify)
+        };
+
+        assert_eq!(
+            futures_lite::future::block_on(
+                notify.wait_until_condition_async_timeout(|v| v == 10, Duration::from_millis(100))
+            ),
+            Err(NotifyTimeoutError::Timeout)
+        );
+
+        // Counters are not dropped until here.
+        drop(weak);
+    }
+
+    #[test]
+    fn current_reads_latest_count() {
+        let (weak, notify) = {
+            let mut builder = WeakCounter::builder();
+            let notify = builder.create_notify();
+            (builder.build(), notify)
+        };
+
+        assert_eq!(notify.current(), 0);
+        let _counter = weak.spawn_upgrade();
+        assert_eq!(notify.current(), 1);
+    }
+
+    #[test]
+    fn changed_returns_new_value_once_count_changes() {
+        // `changed()` only guarantees the new value differs from the last observed one, not
+        // that it's the final, fully-settled count - the background thread notifies after
+        // every `spawn_upgrade`, so the first wakeup can legitimately fire on any
+        // intermediate value. Loop until the count we actually care about shows up.
+        run_wait_until_condition_test(|notify| while notify.changed().unwrap() != 10 {});
+    }
+
+    #[test]
+    fn changed_timeout_can_timeout() {
+        let (weak, notify) = {
+            let mut builder = WeakCounter::builder();
+            let notify = builder.create_notify();
+            (builder.build(), notify)
+        };
+
+        assert_eq!(
+            notify.changed_timeout(Duration::from_millis(100)),
+            Err(NotifyTimeoutError::Timeout)
+        );
+
+        // Counters are not dropped until here.
+        drop(weak);
+    }
+
+    #[test]
+    fn select_returns_index_of_handle_that_fired() {
+        let (weak_a, notify_a) = {
+            let mut builder = WeakCounter::builder();
+            let notify = builder.create_notify();
+            (builder.build(), notify)
+        };
+        let (weak_b, notify_b) = {
+            let mut builder = WeakCounter::builder();
+            let notify = builder.create_notify();
+            (builder.build(), notify)
+        };
+
+        let join_handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            weak_b.spawn_upgrade()
+        });
+
+        let index = NotifyHandle::select(&[&notify_a, &notify_b], |v| v == 1).unwrap();
+        assert_eq!(index, 1);
+
+        drop(weak_a);
+        join_handle.join().unwrap();
+    }
+
+    #[test]
+    fn select_with_timeout_can_timeout() {
+        let (weak, notify) = {
+            let mut builder = WeakCounter::builder();
+            let notify = builder.create_notify();
+            (builder.build(), notify)
+        };
+
+        assert_eq!(
+            NotifyHandle::select_timeout(&[&notify], |v| v == 10, Duration::from_millis(100)),
+            Err(NotifyTimeoutError::Timeout)
+        );
+
+        // Counters are not dropped until here.
+        drop(weak);
+    }
+
+    #[test]
+    fn check_condition_does_not_block() {
+        let mut builder = WeakCounter::builder();
+        let notify = builder.create_notify();
+        let weak = builder.build();
+
+        assert!(!notify.check_condition(|v| v == 1));
+        let _counter = weak.spawn_upgrade();
+        assert!(notify.check_condition(|v| v == 1));
+    }
+
+    #[test]
+    fn is_connected_reflects_linked_counters() {
+        let mut builder = WeakCounter::builder();
+        let notify = builder.create_notify();
+        let weak = builder.build();
+
+        assert!(notify.is_connected());
+        drop(weak);
+        assert!(!notify.is_connected());
+    }
+
+    #[test]
+    fn notify_handle_count_tracks_live_handles() {
+        let mut builder = WeakCounter::builder();
+        let notify1 = builder.create_notify();
+        assert_eq!(notify1.waiter_count(), 0);
+
+        let weak = builder.build();
+        assert_eq!(weak.notify_handle_count(), 1);
+
+        {
+            let mut builder2 = WeakCounter::builder();
+            let _notify2 = builder2.create_notify();
+            let weak2 = builder2.build();
+            assert_eq!(weak2.notify_handle_count(), 1);
+        }
+
+        drop(notify1);
+        assert_eq!(weak.notify_handle_count(), 0);
+    }
 }
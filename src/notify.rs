@@ -1,24 +1,86 @@
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::mpsc;
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
 use std::sync::Arc;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(not(feature = "std"))]
+use core::ptr;
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::AtomicPtr;
+#[cfg(not(feature = "std"))]
+use core::task::{Poll, Waker};
+
+#[cfg(feature = "std")]
+use std::future::Future;
+#[cfg(feature = "std")]
+use std::pin::Pin;
+#[cfg(feature = "std")]
 use std::time::{Duration, Instant};
+
+#[cfg(feature = "std")]
+use async_io::Timer;
+#[cfg(feature = "std")]
+use event_listener::Event;
+#[cfg(feature = "std")]
+use futures_lite::future;
+#[cfg(feature = "std")]
 use thiserror::Error;
 
 /// Struct that enables functionality like waiting to be notified
 /// when the count of a [`crate::Counter`] or [`crate::WeakCounter`] changes.
+///
+/// Besides the blocking [`NotifyHandle::wait_until_condition`] family (which requires the
+/// `std` feature, since it parks an OS thread), an async equivalent is always available
+/// through [`NotifyHandle::wait_until_condition_async`] for use inside async runtimes (eg.
+/// smol, tokio) or bare `no_std` executors without blocking an OS thread.
+///
+/// For callers that don't have a fixed condition to check, [`NotifyHandle::changed_async`] and
+/// [`NotifyHandle::current`] offer a lower-level, watch-style primitive: `current` reads
+/// the latest count, and `changed_async` awaits the next time it differs from the value this
+/// handle last observed (tracked via `last_seen`).
+///
+/// [`NotifyHandle::select`] waits on several handles at once, eg. to track multiple
+/// independent resource pools without spawning a thread per pool. Like the rest of the
+/// blocking API, it requires the `std` feature.
 #[derive(Debug)]
 pub struct NotifyHandle {
-    receiver: mpsc::Receiver<()>,
-    should_send: Arc<AtomicBool>,
+    notifier: Arc<Notifier>,
     counter: Arc<AtomicUsize>,
+    last_seen: AtomicUsize,
 }
 
+#[cfg(feature = "std")]
 #[derive(Error, Debug, PartialEq, Clone, Copy)]
 pub enum NotifyError {
     #[error("All linked senders are disconnected, therefore count will never change!")]
     Disconnected,
 }
 
+/// `thiserror`'s `Error` derive needs `std`, so without the `std` feature this enum (and
+/// its `Display` impl) are implemented by hand instead.
+#[cfg(not(feature = "std"))]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NotifyError {
+    Disconnected,
+}
+
+#[cfg(not(feature = "std"))]
+impl core::fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NotifyError::Disconnected => write!(
+                f,
+                "All linked senders are disconnected, therefore count will never change!"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 #[derive(Error, Debug, PartialEq, Clone, Copy)]
 pub enum NotifyTimeoutError {
     #[error("All linked senders are disconnected, therefore count will never change!")]
@@ -27,34 +89,207 @@ pub enum NotifyTimeoutError {
     Timeout,
 }
 
-/// Struct that can send signals to the [`NotifyHandle`].
-#[derive(Debug, Clone)]
+#[cfg(feature = "std")]
+impl From<NotifyError> for NotifyTimeoutError {
+    fn from(err: NotifyError) -> Self {
+        match err {
+            NotifyError::Disconnected => NotifyTimeoutError::Disconnected,
+        }
+    }
+}
+
+/// The notification primitive shared by every [`NotifySender`] / [`NotifyHandle`] pair
+/// linked to a single count. With the `std` feature, waking is done through an
+/// `event-listener` [`Event`]; without it (`no_std` + `alloc`), waking is done through a
+/// lock-free intrusive list of [`Waker`]s, since `Event`'s backend needs OS thread parking.
+/// Either way it tracks a count of still-live senders so a [`NotifyHandle`] can tell a
+/// quiescent count from one that can never change again, a count of currently-blocked
+/// waiters, and a count of still-live [`NotifyHandle`]s.
+#[derive(Debug)]
+struct Notifier {
+    #[cfg(feature = "std")]
+    event: Event,
+    #[cfg(not(feature = "std"))]
+    waiters: WakerList,
+    sender_count: AtomicUsize,
+    waiter_count: AtomicUsize,
+    handle_count: AtomicUsize,
+}
+
+impl Notifier {
+    #[cfg(feature = "std")]
+    fn new() -> Arc<Notifier> {
+        Arc::new(Notifier {
+            event: Event::new(),
+            sender_count: AtomicUsize::new(1),
+            waiter_count: AtomicUsize::new(0),
+            handle_count: AtomicUsize::new(0),
+        })
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn new() -> Arc<Notifier> {
+        Arc::new(Notifier {
+            waiters: WakerList::new(),
+            sender_count: AtomicUsize::new(1),
+            waiter_count: AtomicUsize::new(0),
+            handle_count: AtomicUsize::new(0),
+        })
+    }
+
+    #[cfg(feature = "std")]
+    fn notify_waiters(&self) {
+        self.event.notify(usize::MAX);
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn notify_waiters(&self) {
+        self.waiters.drain_and_wake();
+    }
+
+    fn is_disconnected(&self) -> bool {
+        self.sender_count.load(Ordering::SeqCst) == 0
+    }
+
+    /// Register a waiter, returning a guard that un-registers it on drop.
+    fn track_waiter(&self) -> WaiterGuard<'_> {
+        WaiterGuard::new(&self.waiter_count)
+    }
+}
+
+/// RAII guard that keeps [`Notifier::waiter_count`] accurate regardless of which of the
+/// several return paths a blocking/async wait exits through.
+struct WaiterGuard<'a> {
+    count: &'a AtomicUsize,
+}
+
+impl<'a> WaiterGuard<'a> {
+    fn new(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        WaiterGuard { count }
+    }
+}
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A single, cheaply-clonable handle held by every [`crate::Counter`] / [`crate::WeakCounter`]
+/// derived from the same lineage. Unlike a dedicated channel per [`NotifyHandle`], mutation
+/// sites only ever need to notify through their own `NotifySender`, regardless of how many
+/// [`NotifyHandle`]s are attached - cloning/dropping it is what keeps the linked `Notifier`'s
+/// sender count accurate.
+#[derive(Debug)]
 pub(crate) struct NotifySender {
-    should_send: Arc<AtomicBool>,
-    sender: mpsc::Sender<()>,
+    notifier: Arc<Notifier>,
+}
+
+impl NotifySender {
+    /// Create a new [`NotifySender`], representing the first linked instance.
+    pub(crate) fn new() -> NotifySender {
+        NotifySender {
+            notifier: Notifier::new(),
+        }
+    }
+
+    /// Notify every [`NotifyHandle`] linked to this sender that the count has changed.
+    pub(crate) fn notify(&self) {
+        self.notifier.notify_waiters();
+    }
+
+    /// Create a [`NotifyHandle`] linked to this sender's notifier and the given count.
+    pub(crate) fn create_handle(&self, counter: Arc<AtomicUsize>) -> NotifyHandle {
+        let last_seen = counter.load(Ordering::SeqCst);
+        self.notifier.handle_count.fetch_add(1, Ordering::SeqCst);
+        NotifyHandle {
+            notifier: Arc::clone(&self.notifier),
+            counter,
+            last_seen: AtomicUsize::new(last_seen),
+        }
+    }
+
+    /// The number of [`NotifyHandle`]s currently linked to this sender.
+    pub(crate) fn handle_count(&self) -> usize {
+        self.notifier.handle_count.load(Ordering::SeqCst)
+    }
+}
+
+impl Clone for NotifySender {
+    fn clone(&self) -> Self {
+        self.notifier.sender_count.fetch_add(1, Ordering::SeqCst);
+        NotifySender {
+            notifier: Arc::clone(&self.notifier),
+        }
+    }
+}
+
+impl Drop for NotifySender {
+    fn drop(&mut self) {
+        // If we were the last sender, wake every waiter so they can observe that the
+        // count is now disconnected instead of waiting forever.
+        if self.notifier.sender_count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.notifier.notify_waiters();
+        }
+    }
+}
+
+impl Drop for NotifyHandle {
+    fn drop(&mut self) {
+        self.notifier.handle_count.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl NotifyHandle {
-    /// Create a new [`NotifyHandle`] with a link to the associated count.
-    pub(crate) fn new(counter: Arc<AtomicUsize>) -> (NotifyHandle, NotifySender) {
-        // Create a new "rendezvous channel". Note that we don't
-        // buffer any data in the channel, so memory won't grow if
-        // no-one is receiving any data.
-        let (sender, receiver) = mpsc::channel();
-        let should_send = Arc::new(AtomicBool::new(false));
-        (
-            NotifyHandle {
-                receiver,
-                should_send: Arc::clone(&should_send),
-                counter,
-            },
-            NotifySender {
-                sender,
-                should_send,
-            },
-        )
+    /// Read the count linked to this handle. This method is inherently racey - assume
+    /// the count will have changed once the value is observed.
+    #[inline]
+    pub fn current(&self) -> usize {
+        self.counter.load(Ordering::SeqCst)
     }
 
+    /// Check `condition` against the current count without blocking.
+    pub fn check_condition(&self, condition: impl Fn(usize) -> bool) -> bool {
+        condition(self.current())
+    }
+
+    /// Whether any [`crate::Counter`] / [`crate::WeakCounter`] is still linked to this
+    /// handle, ie. whether the count can still ever change. Unlike
+    /// [`NotifyHandle::wait_until_condition_async`], this never blocks or awaits.
+    pub fn is_connected(&self) -> bool {
+        !self.is_disconnected()
+    }
+
+    /// The number of threads/tasks currently waiting in [`NotifyHandle::wait_until_condition`]
+    /// (or one of its siblings) on this handle's underlying notifier.
+    pub fn waiter_count(&self) -> usize {
+        self.notifier.waiter_count.load(Ordering::SeqCst)
+    }
+
+    /// Async equivalent of [`NotifyHandle::changed`] (or, under `no_std`, the only way to
+    /// await a change at all): awaits until the count differs from the value this handle
+    /// last observed (or its value at creation, for the first call), returning the new value.
+    pub async fn changed_async(&self) -> Result<usize, NotifyError> {
+        let last_seen = self.last_seen.load(Ordering::SeqCst);
+        self.wait_until_condition_async(|v| v != last_seen).await?;
+        Ok(self.mark_seen())
+    }
+
+    /// Record the current count as the last value observed by this handle, returning it.
+    fn mark_seen(&self) -> usize {
+        let current = self.current();
+        self.last_seen.store(current, Ordering::SeqCst);
+        current
+    }
+
+    fn is_disconnected(&self) -> bool {
+        self.notifier.is_disconnected()
+    }
+}
+
+#[cfg(feature = "std")]
+impl NotifyHandle {
     /// Block the current thread until the condition is true. This is
     /// different than spin-looping since the current thread will use channels
     /// internally to be notified when the counter changes.
@@ -80,89 +315,590 @@ impl NotifyHandle {
         self.wait_until_condition_inner(condition, Some(timeout))
     }
 
+    /// Async equivalent of [`NotifyHandle::wait_until_condition`], for use inside an
+    /// async runtime instead of blocking the calling OS thread.
+    pub async fn wait_until_condition_async(
+        &self,
+        condition: impl Fn(usize) -> bool,
+    ) -> Result<(), NotifyError> {
+        self.wait_until_condition_async_inner(&condition).await
+    }
+
+    /// [`NotifyHandle::wait_until_condition_async`] with a timeout.
+    pub async fn wait_until_condition_async_timeout(
+        &self,
+        condition: impl Fn(usize) -> bool,
+        timeout: Duration,
+    ) -> Result<(), NotifyTimeoutError> {
+        future::or(
+            async {
+                self.wait_until_condition_async_inner(&condition)
+                    .await
+                    .map_err(NotifyTimeoutError::from)
+            },
+            async {
+                Timer::after(timeout).await;
+                Err(NotifyTimeoutError::Timeout)
+            },
+        )
+        .await
+    }
+
+    /// Block the current thread until the count differs from the value this handle last
+    /// observed (or its value at creation, for the first call), returning the new value.
+    pub fn changed(&self) -> Result<usize, NotifyError> {
+        let last_seen = self.last_seen.load(Ordering::SeqCst);
+        self.wait_until_condition(|v| v != last_seen)?;
+        Ok(self.mark_seen())
+    }
+
+    /// [`NotifyHandle::changed`] with a timeout.
+    pub fn changed_timeout(&self, timeout: Duration) -> Result<usize, NotifyTimeoutError> {
+        let last_seen = self.last_seen.load(Ordering::SeqCst);
+        self.wait_until_condition_timeout(|v| v != last_seen, timeout)?;
+        Ok(self.mark_seen())
+    }
+
+    /// [`NotifyHandle::changed_async`] with a timeout.
+    pub async fn changed_async_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<usize, NotifyTimeoutError> {
+        let last_seen = self.last_seen.load(Ordering::SeqCst);
+        self.wait_until_condition_async_timeout(|v| v != last_seen, timeout)
+            .await?;
+        Ok(self.mark_seen())
+    }
+
+    /// Block the current thread until `condition` is true for at least one of the given
+    /// handles, returning the index (into `handles`) of the one that fired. Analogous to
+    /// the mpmc `select` machinery, but for [`NotifyHandle`]s.
+    ///
+    /// Only returns `Disconnected` once *every* sender linked to *every* handle is gone.
+    pub fn select(
+        handles: &[&NotifyHandle],
+        condition: impl Fn(usize) -> bool,
+    ) -> Result<usize, NotifyError> {
+        Self::select_inner(handles, condition, None).map_err(|e| match e {
+            NotifyTimeoutError::Disconnected => NotifyError::Disconnected,
+            NotifyTimeoutError::Timeout => {
+                panic!("Timeout error from select without timeout!")
+            }
+        })
+    }
+
+    /// [`NotifyHandle::select`] with a timeout.
+    pub fn select_timeout(
+        handles: &[&NotifyHandle],
+        condition: impl Fn(usize) -> bool,
+        timeout: Duration,
+    ) -> Result<usize, NotifyTimeoutError> {
+        Self::select_inner(handles, condition, Some(timeout))
+    }
+
+    fn select_inner(
+        handles: &[&NotifyHandle],
+        condition: impl Fn(usize) -> bool,
+        timeout: Option<Duration>,
+    ) -> Result<usize, NotifyTimeoutError> {
+        assert!(
+            !handles.is_empty(),
+            "NotifyHandle::select requires at least one handle"
+        );
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        macro_rules! return_if_any_satisfied {
+            () => {
+                if let Some(index) = handles
+                    .iter()
+                    .position(|handle| condition(handle.counter.load(Ordering::SeqCst)))
+                {
+                    return Ok(index);
+                }
+            };
+        }
+
+        loop {
+            return_if_any_satisfied!();
+
+            // Register a listener on every handle *before* re-checking below, otherwise a
+            // mutation that happens between the first check and registration would be
+            // missed (the lost-wakeup race).
+            let listeners: Vec<_> = handles
+                .iter()
+                .map(|handle| handle.notifier.event.listen())
+                .collect();
+            let _waiter_guards: Vec<_> = handles
+                .iter()
+                .map(|handle| handle.notifier.track_waiter())
+                .collect();
+
+            return_if_any_satisfied!();
+
+            // Only give up once every sender across every handle is gone.
+            if handles.iter().all(|handle| handle.is_disconnected()) {
+                return_if_any_satisfied!();
+                return Err(NotifyTimeoutError::Disconnected);
+            }
+
+            let combined = combine_listeners(listeners);
+            let woken = match deadline {
+                Some(deadline) => future::block_on(future::or(
+                    async {
+                        combined.await;
+                        true
+                    },
+                    async {
+                        Timer::at(deadline).await;
+                        false
+                    },
+                )),
+                None => {
+                    future::block_on(combined);
+                    true
+                }
+            };
+
+            if !woken {
+                return_if_any_satisfied!();
+                return Err(NotifyTimeoutError::Timeout);
+            }
+        }
+    }
+
     fn wait_until_condition_inner(
         &self,
         condition: impl Fn(usize) -> bool,
         timeout: Option<Duration>,
     ) -> Result<(), NotifyTimeoutError> {
-        let start = Instant::now();
-
-        // Drain all messages in the channel before turning sends on again.
-        while let Ok(()) = self.receiver.try_recv() {}
-        self.should_send.store(true, Ordering::SeqCst);
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
 
         macro_rules! return_if_condition {
             () => {
                 if condition(self.counter.load(Ordering::SeqCst)) {
-                    self.should_send.store(false, Ordering::SeqCst);
                     return Ok(());
                 }
             };
         }
 
-        return_if_condition!();
         loop {
-            // Drain all elements from the channel until it's empty. If there were no
-            // elements drained, we block on `recv()`.
-            let recv_result = {
-                let mut received_at_least_once = false;
-                loop {
-                    match self.receiver.try_recv() {
-                        Ok(()) => received_at_least_once = true,
-                        Err(mpsc::TryRecvError::Empty) => {
-                            if received_at_least_once {
-                                break Ok(());
-                            }
-
-                            if let Some(timeout) = timeout {
-                                let remaining_time = if let Some(remaining_time) =
-                                    start.elapsed().checked_sub(timeout)
-                                {
-                                    remaining_time
-                                } else {
-                                    break Err(mpsc::RecvTimeoutError::Timeout);
-                                };
-
-                                break self.receiver.recv_timeout(remaining_time);
-                            } else {
-                                break self
-                                    .receiver
-                                    .recv()
-                                    .map_err(|_| mpsc::RecvTimeoutError::Disconnected);
-                            }
-                        }
-                        Err(mpsc::TryRecvError::Disconnected) => {
-                            break Err(mpsc::RecvTimeoutError::Disconnected)
-                        }
-                    }
+            return_if_condition!();
+
+            // Register a listener *before* re-checking the condition below, otherwise a
+            // mutation that happens between the first check and registration would be
+            // missed (the lost-wakeup race).
+            let listener = self.notifier.event.listen();
+            let _waiter_guard = self.notifier.track_waiter();
+
+            return_if_condition!();
+
+            // If there are no senders left, the count will never change again, so
+            // there's no point listening any further.
+            if self.is_disconnected() {
+                return_if_condition!();
+                return Err(NotifyTimeoutError::Disconnected);
+            }
+
+            let timed_out = match deadline {
+                Some(deadline) => !listener.wait_deadline(deadline),
+                None => {
+                    listener.wait();
+                    false
                 }
             };
 
-            // If the receiver thread is disconnected, then the counter
-            // will never change again.
-            if let Err(err) = recv_result {
-                // We should check if the condition is satisfied one last time, then
-                // return Disconnected if still unsatisfied, since the condition will
-                // never be met.
+            if timed_out {
                 return_if_condition!();
+                return Err(NotifyTimeoutError::Timeout);
+            }
+        }
+    }
 
-                self.should_send.store(false, Ordering::SeqCst);
-                return Err(match err {
-                    mpsc::RecvTimeoutError::Disconnected => NotifyTimeoutError::Disconnected,
-                    mpsc::RecvTimeoutError::Timeout => NotifyTimeoutError::Timeout,
-                });
+    async fn wait_until_condition_async_inner(
+        &self,
+        condition: &impl Fn(usize) -> bool,
+    ) -> Result<(), NotifyError> {
+        loop {
+            if condition(self.counter.load(Ordering::SeqCst)) {
+                return Ok(());
             }
 
-            return_if_condition!();
+            // Register a listener *before* re-checking the condition below, otherwise a
+            // mutation that happens between the first check and registration would be
+            // missed (the lost-wakeup race).
+            let listener = self.notifier.event.listen();
+            let _waiter_guard = self.notifier.track_waiter();
+
+            if condition(self.counter.load(Ordering::SeqCst)) {
+                return Ok(());
+            }
+
+            if self.is_disconnected() {
+                return if condition(self.counter.load(Ordering::SeqCst)) {
+                    Ok(())
+                } else {
+                    Err(NotifyError::Disconnected)
+                };
+            }
+
+            listener.await;
         }
     }
 }
 
-impl NotifySender {
-    /// Notify the handle.
-    pub(crate) fn notify(&self) {
-        if self.should_send.load(Ordering::SeqCst) {
-            let _ = self.sender.send(());
+/// Fold a non-empty set of `event-listener` listeners into a single future that resolves
+/// as soon as *any* of them fires, for use by [`NotifyHandle::select`].
+#[cfg(feature = "std")]
+fn combine_listeners(
+    listeners: Vec<event_listener::EventListener>,
+) -> Pin<Box<dyn Future<Output = ()>>> {
+    let mut listeners = listeners.into_iter();
+    let first = listeners
+        .next()
+        .expect("combine_listeners requires at least one listener");
+    listeners.fold(
+        Box::pin(first) as Pin<Box<dyn Future<Output = ()>>>,
+        |acc, listener| Box::pin(future::or(acc, listener)),
+    )
+}
+
+#[cfg(not(feature = "std"))]
+impl NotifyHandle {
+    /// Async equivalent of the blocking `wait_until_condition` available with the `std`
+    /// feature. Under `no_std`, this is the only way to wait for a condition: it polls
+    /// against a lock-free list of registered [`Waker`]s rather than parking an OS thread,
+    /// so it works with any `core`-only executor.
+    ///
+    /// Holds a [`WaiterGuard`] for the lifetime of the returned future (not per-poll), so
+    /// [`NotifyHandle::waiter_count`] reflects one outstanding logical wait regardless of
+    /// how many times the future is polled.
+    pub async fn wait_until_condition_async(
+        &self,
+        condition: impl Fn(usize) -> bool,
+    ) -> Result<(), NotifyError> {
+        let mut waiter_guard: Option<WaiterGuard<'_>> = None;
+
+        core::future::poll_fn(move |cx| {
+            if condition(self.current()) {
+                return Poll::Ready(Ok(()));
+            }
+
+            if waiter_guard.is_none() {
+                waiter_guard = Some(self.notifier.track_waiter());
+            }
+
+            // Register this poll's waker *before* re-checking below, otherwise a
+            // mutation that happens between the first check and registration would be
+            // missed (the lost-wakeup race). Every `Pending` return must re-register,
+            // since the waker handed to us can change between polls.
+            self.notifier.waiters.push(cx.waker().clone());
+
+            if condition(self.current()) {
+                return Poll::Ready(Ok(()));
+            }
+
+            if self.is_disconnected() {
+                return if condition(self.current()) {
+                    Poll::Ready(Ok(()))
+                } else {
+                    Poll::Ready(Err(NotifyError::Disconnected))
+                };
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// A single intrusive node in the lock-free wakeup list, holding one waiter's [`Waker`].
+#[cfg(not(feature = "std"))]
+struct WakerNode {
+    waker: Waker,
+    next: AtomicPtr<WakerNode>,
+}
+
+/// Lock-free, singly-linked list of waiters' [`Waker`]s, used in place of `event-listener`'s
+/// std backend so the notify subsystem works under `#![no_std]` + `alloc`. Nodes are pushed
+/// with a lock-free compare-and-swap onto the head, and a notification atomically swaps out
+/// the whole list and wakes every node it finds - so a node left behind by a cancelled
+/// future is simply woken (harmlessly, since nothing is polling it any more) the next time
+/// the count changes, rather than being actively removed.
+///
+/// A node is never freed the instant it's swapped off `head`: that would let its address be
+/// handed back out by the allocator while a concurrent `push` that read the old `head` value
+/// is still mid compare-and-swap against it - a classic ABA hazard that could corrupt the
+/// list. Instead, each `drain_and_wake` retires its drained chain into one of two `retired`
+/// generations (alternating on every call) and frees whatever was left in the generation two
+/// calls ago, which by then a racing `push` has had a full notification cycle to finish
+/// against. That bounds how many dead nodes can pile up to roughly two generations' worth
+/// instead of growing without bound for the life of the list, while keeping the same delayed-
+/// free protection against the ABA hazard. The last generation still outstanding is freed in
+/// `Drop`.
+#[cfg(not(feature = "std"))]
+#[derive(Debug)]
+struct WakerList {
+    head: AtomicPtr<WakerNode>,
+    retired: [AtomicPtr<WakerNode>; 2],
+    retired_slot: AtomicUsize,
+}
+
+#[cfg(not(feature = "std"))]
+impl WakerList {
+    fn new() -> Self {
+        WakerList {
+            head: AtomicPtr::new(ptr::null_mut()),
+            retired: [AtomicPtr::new(ptr::null_mut()), AtomicPtr::new(ptr::null_mut())],
+            retired_slot: AtomicUsize::new(0),
+        }
+    }
+
+    /// Push `waker` onto the front of the list.
+    fn push(&self, waker: Waker) {
+        let node = Box::into_raw(Box::new(WakerNode {
+            waker,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }));
+
+        Self::push_chain(&self.head, node, node);
+    }
+
+    /// Compare-and-swap the chain `[head_node ..= tail_node]` onto the front of `list`.
+    fn push_chain(list: &AtomicPtr<WakerNode>, head_node: *mut WakerNode, tail_node: *mut WakerNode) {
+        let mut current = list.load(Ordering::Acquire);
+        loop {
+            // Safety: `tail_node` is either freshly allocated (from `push`) or was just
+            // swapped off `head`/a `retired` slot, so in both cases we're the only writer of
+            // its `next` pointer until this CAS publishes it.
+            unsafe { (*tail_node).next.store(current, Ordering::Relaxed) };
+
+            match list.compare_exchange_weak(current, head_node, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Atomically take every registered node and wake each one. The drained chain is then
+    /// retired into the next `retired` generation rather than freed immediately (see the
+    /// struct docs), and whichever chain already occupied that generation (left there by a
+    /// `drain_and_wake` from two calls ago) is freed now that it's had a full cycle to age out.
+    fn drain_and_wake(&self) {
+        let head = self.head.swap(ptr::null_mut(), Ordering::AcqRel);
+        if head.is_null() {
+            return;
+        }
+
+        let mut tail = head;
+        loop {
+            // Safety: this chain was just swapped off `head`, so it's no longer reachable
+            // from there; nothing else can be concurrently mutating these nodes.
+            unsafe { (*tail).waker.wake_by_ref() };
+
+            let next = unsafe { (*tail).next.load(Ordering::Relaxed) };
+            if next.is_null() {
+                break;
+            }
+            tail = next;
         }
+
+        let slot = self.retired_slot.fetch_add(1, Ordering::AcqRel) % self.retired.len();
+        Self::free_chain(self.retired[slot].swap(ptr::null_mut(), Ordering::AcqRel));
+        Self::push_chain(&self.retired[slot], head, tail);
+    }
+
+    /// Free every node in the (null-terminated) chain starting at `node`.
+    fn free_chain(mut node: *mut WakerNode) {
+        while !node.is_null() {
+            // Safety: every node was allocated via `Box::into_raw` in `push` and is only
+            // ever reclaimed once it's aged out of the `retired` generations (or the whole
+            // `WakerList` is being dropped), so reclaiming it as a `Box` is sound.
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next.load(Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Drop for WakerList {
+    fn drop(&mut self) {
+        Self::free_chain(self.head.swap(ptr::null_mut(), Ordering::AcqRel));
+        for slot in &self.retired {
+            Self::free_chain(slot.swap(ptr::null_mut(), Ordering::AcqRel));
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "std")))]
+mod waker_list_tests {
+    use super::*;
+    use alloc::task::Wake;
+    use core::sync::atomic::AtomicUsize;
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        (counter, waker)
+    }
+
+    #[test]
+    fn drain_and_wake_on_empty_list_is_a_no_op() {
+        let list = WakerList::new();
+        list.drain_and_wake();
+    }
+
+    #[test]
+    fn drain_and_wake_wakes_every_pushed_waker() {
+        let list = WakerList::new();
+        let (counter_a, waker_a) = counting_waker();
+        let (counter_b, waker_b) = counting_waker();
+        list.push(waker_a);
+        list.push(waker_b);
+
+        list.drain_and_wake();
+
+        assert_eq!(counter_a.0.load(Ordering::Relaxed), 1);
+        assert_eq!(counter_b.0.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn nodes_left_behind_are_freed_without_waking_on_drop() {
+        let (counter, waker) = counting_waker();
+        let list = WakerList::new();
+        list.push(waker);
+
+        drop(list);
+
+        assert_eq!(counter.0.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn retired_nodes_from_drain_and_wake_are_freed_on_drop() {
+        let (_counter, waker) = counting_waker();
+        let list = WakerList::new();
+        list.push(waker);
+        list.drain_and_wake();
+
+        // Drop must free the retired chain too, not just `head` (which is empty by now).
+        drop(list);
+    }
+
+    struct DropCountingWaker(Arc<AtomicUsize>);
+
+    impl Wake for DropCountingWaker {
+        fn wake(self: Arc<Self>) {}
+        fn wake_by_ref(self: &Arc<Self>) {}
+    }
+
+    impl Drop for DropCountingWaker {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn old_retired_generations_are_freed_instead_of_growing_unbounded() {
+        let list = WakerList::new();
+        let drop_count = Arc::new(AtomicUsize::new(0));
+
+        let pushes = 10;
+        for _ in 0..pushes {
+            let waker = Waker::from(Arc::new(DropCountingWaker(Arc::clone(&drop_count))));
+            list.push(waker);
+            list.drain_and_wake();
+        }
+
+        // Only the last couple of `drain_and_wake` generations may still be un-freed;
+        // everything older must have been reclaimed already rather than piling up forever.
+        let live = pushes - drop_count.load(Ordering::Relaxed);
+        assert!(live <= 2, "expected at most 2 live nodes, found {live}");
+
+        drop(list);
+        assert_eq!(drop_count.load(Ordering::Relaxed), pushes);
+    }
+}
+
+/// Exercises the public `no_std` async API end-to-end (rather than just the `WakerList`
+/// primitive it's built on) by polling its futures by hand with a no-op [`Waker`], since
+/// there's no executor available without the `std` feature. `cfg(test)` still links `std`,
+/// so this only needs `core`/`alloc` APIs to stay representative of a real `no_std` caller.
+#[cfg(all(test, not(feature = "std")))]
+mod no_std_async_tests {
+    use super::*;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, RawWaker, RawWakerVTable};
+
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+
+    /// Poll `future` once against a no-op waker (nothing reads it back; the test instead
+    /// re-polls after driving the counter directly).
+    fn poll_once<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+        let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+        let mut cx = Context::from_waker(&waker);
+        Pin::new(future).poll(&mut cx)
+    }
+
+    #[test]
+    fn wait_until_condition_async_resolves_once_condition_is_met() {
+        let mut builder = crate::WeakCounter::builder();
+        let notify = builder.create_notify();
+        let weak = builder.build();
+
+        let mut future = Box::pin(notify.wait_until_condition_async(|v| v == 1));
+        assert_eq!(poll_once(&mut future), Poll::Pending);
+
+        let _counter = weak.spawn_upgrade();
+
+        assert_eq!(poll_once(&mut future), Poll::Ready(Ok(())));
+    }
+
+    #[test]
+    fn wait_until_condition_async_errors_once_disconnected() {
+        let mut builder = crate::WeakCounter::builder();
+        let notify = builder.create_notify();
+        let weak = builder.build();
+
+        let mut future = Box::pin(notify.wait_until_condition_async(|v| v == 1));
+        assert_eq!(poll_once(&mut future), Poll::Pending);
+
+        drop(weak);
+
+        assert_eq!(poll_once(&mut future), Poll::Ready(Err(NotifyError::Disconnected)));
+    }
+
+    #[test]
+    fn changed_async_resolves_after_count_changes() {
+        let mut builder = crate::WeakCounter::builder();
+        let notify = builder.create_notify();
+        let weak = builder.build();
+
+        let mut future = Box::pin(notify.changed_async());
+        assert_eq!(poll_once(&mut future), Poll::Pending);
+
+        let _counter = weak.spawn_upgrade();
+
+        assert_eq!(poll_once(&mut future), Poll::Ready(Ok(1)));
     }
 }